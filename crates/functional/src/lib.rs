@@ -1,17 +1,250 @@
+use std::any::TypeId;
 use std::borrow::Borrow;
-use std::cell::RefCell;
-use std::ops::DerefMut;
-use std::rc::Rc;
+use std::collections::{HashMap, HashSet};
+use std::marker::PhantomData;
 use yew::{Component, ComponentLink, Html, Properties};
 
+use sync::{Lock, Shared};
+
+/// Thread-safety abstraction for the hook runtime, mirroring the split rustc's `sync` module
+/// makes between its single-threaded and `-Zthreads` pairs: `Shared<T>` is the reference-counted
+/// pointer hook state is passed around as, `Lock<T>` is the interior-mutability cell it's stored
+/// in. The default feature set keeps the zero-overhead `Rc`/`RefCell` pair used everywhere in
+/// this crate today; a `parallel` feature swaps in `Arc`/`parking_lot::Mutex` so hook state can
+/// live behind a handle that's `Send + Sync`, a prerequisite for rendering function components
+/// on a worker thread during parallel SSR.
+///
+/// `DynSend`/`DynSync` are the same trick rustc's `sync` module uses for its `dyn Any` error
+/// values: under the default feature set they're blanket-implemented for every type, so they
+/// cost nothing and every existing hook stays exactly as permissive as before; under `parallel`
+/// they're really just (trait-bound-position) aliases for `Send`/`Sync`, so any concrete type
+/// that ends up boxed into a `HookState`/`SignalNode` trait object must actually be safe to move
+/// to (and call from) a worker thread. The trait *object* types themselves (`AnyValue`,
+/// `NotifyFn`, ...) are separate aliases rather than `dyn Any + DynSend + DynSync` spelled out
+/// inline, because a `dyn Trait` can only carry one non-auto trait - they bound with the real
+/// `Send`/`Sync` (which *are* auto traits) under `parallel`, and with nothing extra otherwise.
+/// Together these are what make `Shared<AnyValue>` (`Arc<...>`) itself `Send` under `parallel`,
+/// which is what lets `HookState` (see `render_with_hook_state`) be handed to a worker thread as
+/// an owned value instead of only ever living in `CURRENT_HOOK`.
+mod sync {
+    #[cfg(not(feature = "parallel"))]
+    mod imp {
+        use std::any::Any;
+        use std::cell::{Ref, RefCell, RefMut};
+        use std::rc::Rc;
+
+        pub type Shared<T> = Rc<T>;
+
+        pub struct Lock<T>(RefCell<T>);
+
+        impl<T> Lock<T> {
+            pub fn new(value: T) -> Self {
+                Lock(RefCell::new(value))
+            }
+
+            pub fn borrow(&self) -> Ref<'_, T> {
+                self.0.borrow()
+            }
+
+            pub fn borrow_mut(&self) -> RefMut<'_, T> {
+                self.0.borrow_mut()
+            }
+
+            pub fn try_borrow_mut(&self) -> Result<RefMut<'_, T>, &'static str> {
+                self.0.try_borrow_mut().map_err(|_| "already borrowed")
+            }
+        }
+
+        pub trait DynSend {}
+        impl<T: ?Sized> DynSend for T {}
+
+        pub trait DynSync {}
+        impl<T: ?Sized> DynSync for T {}
+
+        pub type AnyValue = dyn Any;
+        pub type NotifyFn = dyn Fn();
+        pub type MessageFn = dyn FnOnce() -> bool;
+        pub type ProcessMessageFn = dyn Fn(Box<MessageFn>);
+        pub type DestructorFn = dyn FnOnce();
+    }
+
+    #[cfg(feature = "parallel")]
+    mod imp {
+        use parking_lot::{Mutex, MutexGuard};
+        use std::any::Any;
+        use std::sync::Arc;
+
+        pub type Shared<T> = Arc<T>;
+
+        pub struct Lock<T>(Mutex<T>);
+
+        impl<T> Lock<T> {
+            pub fn new(value: T) -> Self {
+                Lock(Mutex::new(value))
+            }
+
+            pub fn borrow(&self) -> MutexGuard<'_, T> {
+                self.0.lock()
+            }
+
+            pub fn borrow_mut(&self) -> MutexGuard<'_, T> {
+                self.0.lock()
+            }
+
+            pub fn try_borrow_mut(&self) -> Result<MutexGuard<'_, T>, &'static str> {
+                self.0.try_lock().ok_or("already locked")
+            }
+        }
+
+        pub trait DynSend: Send {}
+        impl<T: ?Sized + Send> DynSend for T {}
+
+        pub trait DynSync: Sync {}
+        impl<T: ?Sized + Sync> DynSync for T {}
+
+        pub type AnyValue = dyn Any + Send + Sync;
+        pub type NotifyFn = dyn Fn() + Send + Sync;
+        pub type MessageFn = dyn FnOnce() -> bool + Send;
+        pub type ProcessMessageFn = dyn Fn(Box<MessageFn>) + Send + Sync;
+        pub type DestructorFn = dyn FnOnce() + Send;
+    }
+
+    pub use imp::{
+        AnyValue, DestructorFn, DynSend, DynSync, Lock, MessageFn, NotifyFn, ProcessMessageFn, Shared,
+    };
+}
+
+use sync::{AnyValue, DestructorFn, DynSend, DynSync, MessageFn, NotifyFn, ProcessMessageFn};
+
+/// Lazily-initialized, process-wide storage for state that's identified by plain integers
+/// (`NodeId`/`ProviderId`/`SubscriberId`) rather than anything thread-scoped - `RUNTIME`,
+/// `CONTEXT_STACK`, `CONTEXT_SUBSCRIBERS` and their id counters. Under the `parallel` feature
+/// these have to be one shared instance rather than a `thread_local!` per worker thread: a
+/// `NodeId` allocated while rendering on one worker thread would otherwise be meaningless - or
+/// worse, collide with an unrelated node some other component allocated at the same index on a
+/// different thread - the next time that same component re-renders on a different worker.
+/// `CURRENT_HOOK` deliberately does not move into this: it really is "whatever is rendering on
+/// this thread right now" for the duration of one `render_with_hook_state` call, never read from
+/// a different thread than the one that's currently populating it.
+#[cfg(feature = "parallel")]
+struct Global<T: 'static> {
+    init: fn() -> T,
+    cell: std::sync::OnceLock<Lock<T>>,
+}
+
+#[cfg(feature = "parallel")]
+impl<T: 'static> Global<T> {
+    const fn new(init: fn() -> T) -> Self {
+        Global { init, cell: std::sync::OnceLock::new() }
+    }
+
+    fn with<R>(&self, f: impl FnOnce(&Lock<T>) -> R) -> R {
+        f(self.cell.get_or_init(|| Lock::new((self.init)())))
+    }
+}
+
+/// Identifies one `provide_context` call-site instance (stable across a provider's re-renders,
+/// distinct from every other provider - including other instances of the same component and
+/// other providers of the same `T`), so consumers of the same `T` under unrelated providers
+/// don't notify each other.
+type ProviderId = usize;
+
+/// Identifies one `use_context` call-site instance, stable across that consumer's re-renders, so
+/// its `CONTEXT_SUBSCRIBERS` entry can be found and removed again - on provider switch or
+/// unmount - without having to compare notify closures for identity.
+type SubscriberId = usize;
+
+thread_local! {
+    static CURRENT_HOOK: Lock<Option<HookState>> = Lock::new(None);
+}
+
+// One frame per function component currently rendering, pushed/popped symmetrically with
+// `CURRENT_HOOK` around `T::run`. `use_context` walks it from the top down to find the nearest
+// ancestor that called `provide_context::<T>`.
+#[cfg(not(feature = "parallel"))]
+thread_local! {
+    static CONTEXT_STACK: Lock<Vec<Lock<HashMap<TypeId, (ProviderId, Shared<AnyValue>)>>>> =
+        Lock::new(Vec::new());
+}
+#[cfg(feature = "parallel")]
+static CONTEXT_STACK: Global<Vec<Lock<HashMap<TypeId, (ProviderId, Shared<AnyValue>)>>>> =
+    Global::new(Vec::new);
+
+// Subscribers of a specific (provider, type) pair, notified only when that exact provider's
+// value changes - not by unrelated providers of the same `T` elsewhere in the tree. Keyed by
+// `SubscriberId` (rather than just a `Vec`) so a consumer that switches providers or unmounts can
+// remove exactly its own entry instead of leaking it for the life of the process.
+#[cfg(not(feature = "parallel"))]
 thread_local! {
-    static CURRENT_HOOK: RefCell<Option<HookState>> = RefCell::new(None);
+    static CONTEXT_SUBSCRIBERS: Lock<HashMap<(ProviderId, TypeId), HashMap<SubscriberId, Shared<NotifyFn>>>> =
+        Lock::new(HashMap::new());
+}
+#[cfg(feature = "parallel")]
+static CONTEXT_SUBSCRIBERS: Global<HashMap<(ProviderId, TypeId), HashMap<SubscriberId, Shared<NotifyFn>>>> =
+    Global::new(HashMap::new);
+
+#[cfg(not(feature = "parallel"))]
+thread_local! {
+    static NEXT_PROVIDER_ID: Lock<ProviderId> = Lock::new(0);
+    static NEXT_SUBSCRIBER_ID: Lock<SubscriberId> = Lock::new(0);
+}
+#[cfg(feature = "parallel")]
+static NEXT_PROVIDER_ID: Global<ProviderId> = Global::new(|| 0);
+#[cfg(feature = "parallel")]
+static NEXT_SUBSCRIBER_ID: Global<SubscriberId> = Global::new(|| 0);
+
+fn next_provider_id() -> ProviderId {
+    NEXT_PROVIDER_ID.with(|next| {
+        let mut next = next.borrow_mut();
+        let id = *next;
+        *next += 1;
+        id
+    })
+}
+
+fn next_subscriber_id() -> SubscriberId {
+    NEXT_SUBSCRIBER_ID.with(|next| {
+        let mut next = next.borrow_mut();
+        let id = *next;
+        *next += 1;
+        id
+    })
 }
 
+/// Removes `subscriber`'s entry for `(provider_id, type_id)`, if any. Shared by `use_context`'s
+/// provider-switch and unmount paths so a consumer never has more than one live entry, and never
+/// an entry for a provider it's no longer subscribed to.
+fn unsubscribe_context(provider_id: ProviderId, type_id: TypeId, subscriber: SubscriberId) {
+    CONTEXT_SUBSCRIBERS.with(|subs| {
+        if let Some(subscribers) = subs.borrow_mut().get_mut(&(provider_id, type_id)) {
+            subscribers.remove(&subscriber);
+        }
+    });
+}
+
+// `Send + Sync`-bounded (via `DynSend`/`DynSync`) under the `parallel` feature so a `HookState` -
+// and everything reachable from it - can actually be moved to a worker thread; see
+// `render_with_hook_state`.
 struct HookState {
     counter: usize,
-    process_message: Rc<dyn Fn(Box<dyn FnOnce() -> bool>)>,
-    hooks: Vec<Rc<RefCell<dyn std::any::Any>>>,
+    process_message: Shared<ProcessMessageFn>,
+    hooks: Vec<Shared<Lock<Box<AnyValue>>>>,
+    // Callbacks registered through `register_unmount` to run when this component unmounts:
+    // `use_effect` runs its latest destructor, `use_context` drops its provider subscription.
+    // `change`/`update` only see hook state on a subsequent render, and unmounted components
+    // never get one.
+    unmount: Vec<Shared<NotifyFn>>,
+}
+
+/// Registers `f` to run once, when the component currently rendering is unmounted.
+fn register_unmount(f: impl Fn() + DynSend + DynSync + 'static) {
+    CURRENT_HOOK.with(|hook_state_holder| {
+        let mut hook_state_holder = hook_state_holder.borrow_mut();
+        let hook_state = hook_state_holder
+            .as_mut()
+            .expect("No current hook. Hooks can only be called inside functional components");
+        hook_state.unmount.push(Shared::new(f));
+    });
 }
 
 pub trait FunctionProvider {
@@ -22,24 +255,52 @@ pub trait FunctionProvider {
 pub struct FunctionComponent<T: FunctionProvider> {
     _never: std::marker::PhantomData<T>,
     props: T::TProps,
-    hook_state: RefCell<Option<HookState>>,
+    hook_state: Lock<Option<HookState>>,
+}
+
+/// Renders by installing `hook_state` as `CURRENT_HOOK` on whichever thread calls this, running
+/// `render`, then taking `hook_state` back out - passed and returned by value rather than read
+/// out of a `CURRENT_HOOK` assumed to already hold it. `CURRENT_HOOK` only ever stands in for
+/// "the hook context of whatever is rendering on this thread right now" for the duration of this
+/// call; it is not where `HookState` lives between renders; `FunctionComponent` is. Under the
+/// `parallel` feature `HookState` is `Send`, so a caller can build one on the thread that owns a
+/// `FunctionComponent`, move it across to a worker thread (down a channel, into
+/// `thread::spawn`, ...), and call this function there to actually do the rendering - the worker
+/// only ever sees its own, empty `CURRENT_HOOK` get populated and drained again.
+fn render_with_hook_state<R>(mut hook_state: HookState, render: impl FnOnce() -> R) -> (R, HookState) {
+    hook_state.counter = 0;
+    CURRENT_HOOK.with(|current| {
+        let mut current = current.try_borrow_mut().expect("Nested/concurrent render on this thread");
+        assert!(current.is_none(), "Nested/concurrent render on this thread");
+        *current = Some(hook_state);
+    });
+    let ret = render();
+    let hook_state = CURRENT_HOOK.with(|current| {
+        current
+            .try_borrow_mut()
+            .expect("Nested/concurrent render on this thread")
+            .take()
+            .expect("render consumed CURRENT_HOOK")
+    });
+    (ret, hook_state)
 }
 
 impl<T: 'static> Component for FunctionComponent<T>
 where
     T: FunctionProvider,
 {
-    type Message = Box<dyn FnOnce() -> bool>;
+    type Message = Box<MessageFn>;
     type Properties = T::TProps;
 
     fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
         FunctionComponent {
             _never: std::marker::PhantomData::default(),
             props,
-            hook_state: RefCell::new(Some(HookState {
+            hook_state: Lock::new(Some(HookState {
                 counter: 0,
-                process_message: Rc::new(move |msg| link.send_message(msg)),
+                process_message: Shared::new(move |msg| link.send_message(msg)),
                 hooks: vec![],
+                unmount: vec![],
             })),
         }
     }
@@ -56,80 +317,117 @@ where
 
     //noinspection DuplicatedCode
     fn view(&self) -> Html {
-        // Reset hook
-        self.hook_state
+        let hook_state = self
+            .hook_state
             .try_borrow_mut()
             .expect("Unexpected concurrent/nested view call")
-            .as_mut()
-            .unwrap()
-            .counter = 0;
-        // Load hook
-        CURRENT_HOOK.with(|previous_hook| {
-            std::mem::swap(
-                previous_hook
-                    .try_borrow_mut()
-                    .expect("Previous hook still borrowed")
-                    .deref_mut(),
-                self.hook_state.borrow_mut().deref_mut(),
-            );
-        });
+            .take()
+            .expect("Unexpected concurrent/nested view call");
 
-        let ret = T::run(&self.props);
-
-        // Unload hook
-        CURRENT_HOOK.with(|previous_hook| {
-            std::mem::swap(
-                previous_hook
-                    .try_borrow_mut()
-                    .expect("Previous hook still borrowed")
-                    .deref_mut(),
-                self.hook_state.borrow_mut().deref_mut(),
-            );
+        let (ret, hook_state) = render_with_hook_state(hook_state, || {
+            // Enter a fresh context frame for this render so `provide_context` calls made while
+            // running this component are only visible to it and its descendants.
+            CONTEXT_STACK.with(|stack| stack.borrow_mut().push(Lock::new(HashMap::new())));
+            let ret = T::run(&self.props);
+            CONTEXT_STACK.with(|stack| {
+                stack.borrow_mut().pop();
+            });
+            ret
         });
 
+        *self
+            .hook_state
+            .try_borrow_mut()
+            .expect("Unexpected concurrent/nested view call") = Some(hook_state);
+
         return ret;
     }
+
+    fn destroy(&mut self) {
+        let unmount = self
+            .hook_state
+            .borrow()
+            .as_ref()
+            .map(|state| state.unmount.clone())
+            .unwrap_or_default();
+        for destroy in unmount {
+            destroy();
+        }
+    }
 }
 
-pub fn use_ref<T: 'static, InitialProvider>(initial_value: InitialProvider) -> Rc<RefCell<T>>
+pub fn use_ref<T: 'static + DynSend + DynSync, InitialProvider>(
+    initial_value: InitialProvider,
+) -> Shared<Lock<T>>
 where
     InitialProvider: FnOnce() -> T,
 {
-    type UseRefState<T> = Rc<RefCell<T>>;
+    type UseRefState<T> = Shared<Lock<T>>;
 
     use_hook(
         |state: &mut UseRefState<T>, pretrigger_change_acceptor| {
             let _ignored = || pretrigger_change_acceptor(|_| false); // we need it to be a specific closure type, even if we never use it
             return state.clone();
         },
-        move || Rc::new(RefCell::new(initial_value())),
+        move || Shared::new(Lock::new(initial_value())),
     )
 }
 
-pub fn use_reducer<Action: 'static, Reducer, State: 'static>(
+pub fn use_memo<T: 'static + DynSend + DynSync, F, D: 'static + DynSend + DynSync>(
+    compute: F,
+    deps: D,
+) -> Shared<T>
+where
+    F: FnOnce(&D) -> T,
+    D: PartialEq,
+{
+    struct UseMemoState<T, D> {
+        memoized: Option<(Shared<T>, Shared<D>)>,
+    }
+
+    let deps = Shared::new(deps);
+    use_hook(
+        |state: &mut UseMemoState<T, D>, pretrigger_change_acceptor| {
+            let _ignored = || pretrigger_change_acceptor(|_| false); // we need it to be a specific closure type, even if we never use it
+            let should_recompute = match &state.memoized {
+                Some((_, prev_deps)) => **prev_deps != *deps,
+                None => true,
+            };
+            if should_recompute {
+                let value = Shared::new(compute(&deps));
+                state.memoized = Some((value.clone(), deps));
+                return value;
+            }
+            return state.memoized.as_ref().unwrap().0.clone();
+        },
+        || UseMemoState { memoized: None },
+    )
+}
+
+pub fn use_reducer<Action: 'static, Reducer, State: 'static + DynSend + DynSync>(
     reducer: Reducer,
     initial_state: State,
-) -> (Rc<State>, Box<impl Fn(Action)>)
+) -> (Shared<State>, Box<impl Fn(Action)>)
 where
-    Reducer: Fn(Rc<State>, Action) -> State + 'static,
+    Reducer: Fn(Shared<State>, Action) -> State + 'static,
 {
     return use_reducer_with_init(reducer, initial_state, |a| a);
 }
 
-pub fn use_reducer_with_init<Action: 'static, Reducer, State: 'static, InitialState, InitFn>(
+pub fn use_reducer_with_init<Action: 'static, Reducer, State: 'static + DynSend + DynSync, InitialState, InitFn>(
     reducer: Reducer,
     initial_state: InitialState,
     init: InitFn,
-) -> (Rc<State>, Box<impl Fn(Action)>)
+) -> (Shared<State>, Box<impl Fn(Action)>)
 where
-    Reducer: Fn(Rc<State>, Action) -> State + 'static,
+    Reducer: Fn(Shared<State>, Action) -> State + 'static,
     InitFn: Fn(InitialState) -> State,
 {
     struct UseReducerState<State> {
-        current_state: Rc<State>,
+        current_state: Shared<State>,
     }
     let init = Box::new(init);
-    let reducer = Rc::new(reducer);
+    let reducer = Shared::new(reducer);
     let ret = use_hook(
         |internal_hook_change: &mut UseReducerState<State>, pretrigger_change_runner| {
             return (
@@ -138,7 +436,7 @@ where
                     let reducer = reducer.clone();
                     pretrigger_change_runner(
                         move |internal_hook_change: &mut UseReducerState<State>| {
-                            internal_hook_change.current_state = Rc::new((reducer)(
+                            internal_hook_change.current_state = Shared::new((reducer)(
                                 internal_hook_change.current_state.clone(),
                                 action,
                             ));
@@ -149,19 +447,19 @@ where
             );
         },
         move || UseReducerState {
-            current_state: Rc::new(init(initial_state)),
+            current_state: Shared::new(init(initial_state)),
         },
     );
     return ret;
 }
 
-pub fn use_state<T, F>(initial_state_fn: F) -> (Rc<T>, Box<impl Fn(T)>)
+pub fn use_state<T, F>(initial_state_fn: F) -> (Shared<T>, Box<impl Fn(T)>)
 where
     F: FnOnce() -> T,
-    T: 'static,
+    T: 'static + DynSend + DynSync,
 {
     struct UseStateState<T2> {
-        current: Rc<T2>,
+        current: Shared<T2>,
     }
     return use_hook(
         |prev: &mut UseStateState<T>, hook_update| {
@@ -170,190 +468,97 @@ where
                 current,
                 Box::new(move |o: T| {
                     hook_update(|state: &mut UseStateState<T>| {
-                        state.current = Rc::new(o);
+                        state.current = Shared::new(o);
                         true
                     });
                 }),
             );
         },
         move || UseStateState {
-            current: Rc::new(initial_state_fn()),
+            current: Shared::new(initial_state_fn()),
         },
     );
 }
 
-pub fn use_effect<F, Destructor>(callback: F)
-where
-    F: FnOnce() -> Destructor,
-    Destructor: FnOnce() + 'static,
-{
-    let callback = Box::new(callback);
-    use_effect5(
-        Box::new(|_: &(), _: &(), _: &(), _: &(), _: &()| callback()),
-        (),
-        (),
-        (),
-        (),
-        (),
-    );
+/// A trait for dependency lists that `use_effect` can compare between renders to decide
+/// whether the effect needs to re-run. Implemented for `()`, tuples of arity 1 through 8, and
+/// `Vec<T>`, which removes the arbitrary arity-5 ceiling the old `use_effect1`..`use_effect5`
+/// family imposed.
+pub trait Dependencies: PartialEq + 'static {
+    fn changed(&self, other: &Self) -> bool {
+        self != other
+    }
 }
 
-pub fn use_effect1<F, Destructor, T1>(callback: F, o1: T1)
-where
-    F: FnOnce(&T1) -> Destructor,
-    Destructor: FnOnce() + 'static,
-    T1: PartialEq + 'static,
-{
-    let callback = Box::new(callback);
-    use_effect5(
-        Box::new(|a: &T1, _: &(), _: &(), _: &(), _: &()| callback(a)),
-        o1,
-        (),
-        (),
-        (),
-        (),
-    );
-}
+impl Dependencies for () {}
 
-pub fn use_effect2<F, Destructor, T1, T2>(callback: F, o1: T1, o2: T2)
-where
-    F: FnOnce(&T1, &T2) -> Destructor,
-    Destructor: FnOnce() + 'static,
-    T1: PartialEq + 'static,
-    T2: PartialEq + 'static,
-{
-    let callback = Box::new(callback);
-    use_effect5(
-        Box::new(|a: &T1, b: &T2, _: &(), _: &(), _: &()| callback(a, b)),
-        o1,
-        o2,
-        (),
-        (),
-        (),
-    );
-}
+impl<T: PartialEq + 'static> Dependencies for Vec<T> {}
 
-pub fn use_effect3<F, Destructor, T1, T2, T3>(callback: F, o1: T1, o2: T2, o3: T3)
-where
-    F: FnOnce(&T1, &T2, &T3) -> Destructor,
-    Destructor: FnOnce() + 'static,
-    T1: PartialEq + 'static,
-    T2: PartialEq + 'static,
-    T3: PartialEq + 'static,
-{
-    let callback = Box::new(callback);
-    use_effect5(
-        Box::new(|a: &T1, b: &T2, c: &T3, _: &(), _: &()| callback(a, b, c)),
-        o1,
-        o2,
-        o3,
-        (),
-        (),
-    );
+macro_rules! impl_dependencies_for_tuple {
+    ($($name:ident),+) => {
+        impl<$($name: PartialEq + 'static),+> Dependencies for ($($name,)+) {}
+    };
 }
 
-pub fn use_effect4<F, Destructor, T1, T2, T3, T4>(callback: F, o1: T1, o2: T2, o3: T3, o4: T4)
-where
-    F: FnOnce(&T1, &T2, &T3, &T4) -> Destructor,
-    Destructor: FnOnce() + 'static,
-    T1: PartialEq + 'static,
-    T2: PartialEq + 'static,
-    T3: PartialEq + 'static,
-    T4: PartialEq + 'static,
-{
-    let callback = Box::new(callback);
-    use_effect5(
-        Box::new(|a: &T1, b: &T2, c: &T3, d: &T4, _: &()| callback(a, b, c, d)),
-        o1,
-        o2,
-        o3,
-        o4,
-        (),
-    );
-}
+impl_dependencies_for_tuple!(T1);
+impl_dependencies_for_tuple!(T1, T2);
+impl_dependencies_for_tuple!(T1, T2, T3);
+impl_dependencies_for_tuple!(T1, T2, T3, T4);
+impl_dependencies_for_tuple!(T1, T2, T3, T4, T5);
+impl_dependencies_for_tuple!(T1, T2, T3, T4, T5, T6);
+impl_dependencies_for_tuple!(T1, T2, T3, T4, T5, T6, T7);
+impl_dependencies_for_tuple!(T1, T2, T3, T4, T5, T6, T7, T8);
 
-pub fn use_effect5<F, Destructor, T1, T2, T3, T4, T5>(
-    callback: Box<F>,
-    o1: T1,
-    o2: T2,
-    o3: T3,
-    o4: T4,
-    o5: T5,
-) where
-    F: FnOnce(&T1, &T2, &T3, &T4, &T5) -> Destructor,
-    Destructor: FnOnce() + 'static,
-    T1: PartialEq + 'static,
-    T2: PartialEq + 'static,
-    T3: PartialEq + 'static,
-    T4: PartialEq + 'static,
-    T5: PartialEq + 'static,
+pub fn use_effect<F, D, Destructor>(callback: F, deps: D)
+where
+    F: FnOnce(&D) -> Destructor,
+    Destructor: FnOnce() + DynSend + 'static,
+    D: Dependencies + DynSend + DynSync,
 {
-    struct UseEffectState<T1, T2, T3, T4, T5, Destructor> {
-        o1: Rc<T1>,
-        o2: Rc<T2>,
-        o3: Rc<T3>,
-        o4: Rc<T4>,
-        o5: Rc<T5>,
-        destructor: Option<Box<Destructor>>,
+    struct UseEffectState<D> {
+        deps: Option<Shared<D>>,
+        unmount_registered: bool,
     }
-    let o1 = Rc::new(o1);
-    let o2 = Rc::new(o2);
-    let o3 = Rc::new(o3);
-    let o4 = Rc::new(o4);
-    let o5 = Rc::new(o5);
-    let o1_c = o1.clone();
-    let o2_c = o2.clone();
-    let o3_c = o3.clone();
-    let o4_c = o4.clone();
-    let o5_c = o5.clone();
+    // The destructor lives in its own cell (rather than inside the `use_hook`-managed state)
+    // so `register_unmount` can take and run it later without knowing `Destructor`'s concrete
+    // type - by the time the component unmounts there's no render in progress to downcast
+    // against.
+    let destructor: Shared<Lock<Option<Box<DestructorFn>>>> = use_ref(|| None);
+    let deps = Shared::new(deps);
     use_hook(
-        move |state: &mut UseEffectState<T1, T2, T3, T4, T5, Destructor>, hook_update| {
-            let mut should_update = !(*state.o1 == *o1
-                && *state.o2 == *o2
-                && *state.o3 == *o3
-                && *state.o4 == *o4
-                && *state.o5 == *o5);
+        move |state: &mut UseEffectState<D>, hook_update| {
+            let should_update = match &state.deps {
+                Some(prev) => prev.changed(&deps),
+                None => true,
+            };
+
+            if !state.unmount_registered {
+                state.unmount_registered = true;
+                let destructor = destructor.clone();
+                register_unmount(move || {
+                    if let Some(de) = destructor.borrow_mut().take() {
+                        de();
+                    }
+                });
+            }
 
             if should_update {
-                if let Some(de) = state.destructor.take() {
+                if let Some(de) = destructor.borrow_mut().take() {
                     de();
                 }
-                let new_destructor = callback(
-                    o1.borrow(),
-                    o2.borrow(),
-                    o3.borrow(),
-                    o4.borrow(),
-                    o5.borrow(),
-                );
-                state.o1 = o1.clone();
-                state.o2 = o2.clone();
-                state.o3 = o3.clone();
-                state.o4 = o4.clone();
-                state.o5 = o5.clone();
-                state.destructor.replace(Box::new(new_destructor));
-            } else if state.destructor.is_none() {
-                should_update = true;
-                state.destructor.replace(Box::new(callback(
-                    state.o1.borrow(),
-                    state.o2.borrow(),
-                    state.o3.borrow(),
-                    state.o4.borrow(),
-                    state.o5.borrow(),
-                )));
+                let new_destructor = callback(deps.borrow());
+                state.deps = Some(deps.clone());
+                destructor.borrow_mut().replace(Box::new(new_destructor));
             }
             return move || {
                 if should_update {
-                    hook_update(move |_: &mut UseEffectState<T1, T2, T3, T4, T5, Destructor>| true)
+                    hook_update(move |_: &mut UseEffectState<D>| true)
                 }
             };
         },
         || UseEffectState {
-            o1: o1_c,
-            o2: o2_c,
-            o3: o3_c,
-            o4: o4_c,
-            o5: o5_c,
-            destructor: None,
+            deps: None,
+            unmount_registered: false,
         },
     )();
 }
@@ -364,9 +569,9 @@ pub fn use_hook<InternalHookState, HookRunner, R, InitialStateProvider, Pretrigg
 ) -> R
 where
     HookRunner: FnOnce(&mut InternalHookState, Box<dyn Fn(PretriggerChange)>) -> R,
-    InternalHookState: 'static,
+    InternalHookState: 'static + DynSend + DynSync,
     InitialStateProvider: FnOnce() -> InternalHookState,
-    PretriggerChange: FnOnce(&mut InternalHookState) -> bool,
+    PretriggerChange: FnOnce(&mut InternalHookState) -> bool + DynSend,
 {
     // Extract current hook
     let (hook, process_message) = CURRENT_HOOK.with(|hook_state_holder| {
@@ -382,7 +587,8 @@ where
 
         // Initialize hook if this is the first call
         if hook_pos >= hook_state.hooks.len() {
-            let initial_state = Rc::new(RefCell::new(initial_state_producer()));
+            let boxed: Box<AnyValue> = Box::new(initial_state_producer());
+            let initial_state = Shared::new(Lock::new(boxed));
             hook_state.hooks.push(initial_state);
         }
 
@@ -414,3 +620,414 @@ where
     // it create a callback that takes the mutable hook state.
     hook_runner(&mut hook, trigger)
 }
+
+// ---------------------------------------------------------------------------------------------
+// Reactive signals
+//
+// A small Leptos-style runtime that tracks dependencies automatically instead of requiring
+// callers to hand-list them, as `use_effect`'s `Dependencies` still does. Signals, effects and
+// memos are all nodes in a single arena; reading a signal while some node is "observing" (i.e.
+// running its compute/effect closure) wires up a subscriber edge in both directions, and writing
+// a signal schedules every subscriber to re-run through the owning component's existing
+// `process_message` plumbing.
+// ---------------------------------------------------------------------------------------------
+
+type NodeId = usize;
+
+struct SignalNode {
+    value: Lock<Shared<AnyValue>>,
+    // Nodes that read this node while it was the active observer; notified on write.
+    subscribers: HashSet<NodeId>,
+    // Nodes this node read while it last ran; cleared before every re-run so stale
+    // subscriptions don't linger.
+    dependencies: HashSet<NodeId>,
+    // How to re-run this node (effects and memos only; plain signals have no rerun).
+    rerun: Option<Shared<NotifyFn>>,
+}
+
+#[derive(Default)]
+struct Runtime {
+    nodes: Vec<Option<SignalNode>>,
+    observer_stack: Vec<NodeId>,
+    // Nodes with a rerun already scheduled - enqueued through the owning component's
+    // `process_message`, via `rerun()`, but not yet actually executed - so a diamond (e.g. an
+    // effect fed by two memos that both read the same signal) doesn't enqueue the shared effect
+    // twice before the first enqueued run has had a chance to happen. A node's entry here is
+    // cleared from inside its own rerun body once that deferred message actually runs (see
+    // `create_effect`/`create_memo`), not when `rerun()` merely returns from enqueueing it -
+    // returning from `rerun()` only means "handed off to `process_message`", not "ran".
+    scheduled: HashSet<NodeId>,
+}
+
+impl Runtime {
+    fn alloc(
+        &mut self,
+        value: Shared<AnyValue>,
+        rerun: Option<Shared<NotifyFn>>,
+    ) -> NodeId {
+        self.nodes.push(Some(SignalNode {
+            value: Lock::new(value),
+            subscribers: HashSet::new(),
+            dependencies: HashSet::new(),
+            rerun,
+        }));
+        self.nodes.len() - 1
+    }
+
+    fn node(&self, id: NodeId) -> &SignalNode {
+        self.nodes[id].as_ref().expect("signal node was never allocated")
+    }
+}
+
+// `NodeId`s this allocates are plain indices into `nodes`, so under `parallel` this has to be one
+// process-wide `Runtime`, not a `thread_local!` per worker thread - see `Global`'s doc comment.
+#[cfg(not(feature = "parallel"))]
+thread_local! {
+    static RUNTIME: Lock<Runtime> = Lock::new(Runtime::default());
+}
+#[cfg(feature = "parallel")]
+static RUNTIME: Global<Runtime> = Global::new(Runtime::default);
+
+/// Records a read of `id` as a dependency of whichever node is currently observing (if any).
+fn track_read(id: NodeId) {
+    RUNTIME.with(|rt| {
+        let mut rt = rt.borrow_mut();
+        if let Some(&observer) = rt.observer_stack.last() {
+            rt.nodes[id].as_mut().unwrap().subscribers.insert(observer);
+            rt.nodes[observer].as_mut().unwrap().dependencies.insert(id);
+        }
+    });
+}
+
+/// Runs `f` with `id` pushed onto the observer stack, after first clearing `id`'s previous
+/// dependency edges. Must not hold the `Runtime` borrow while `f` runs, since `f` will itself
+/// re-enter the runtime through `ReadSignal::get`/`WriteSignal::set`.
+fn run_tracked<R>(id: NodeId, f: impl FnOnce() -> R) -> R {
+    RUNTIME.with(|rt| {
+        let mut rt = rt.borrow_mut();
+        let deps = std::mem::take(&mut rt.nodes[id].as_mut().unwrap().dependencies);
+        for dep in deps {
+            if let Some(dep_node) = rt.nodes[dep].as_mut() {
+                dep_node.subscribers.remove(&id);
+            }
+        }
+        rt.observer_stack.push(id);
+    });
+    let result = f();
+    RUNTIME.with(|rt| {
+        rt.borrow_mut().observer_stack.pop();
+    });
+    result
+}
+
+/// Schedules every subscriber of `id` to re-run. Unlike a synchronous call stack, a node's rerun
+/// is an enqueued component message (see `create_effect`/`create_memo`) that may not actually run
+/// until long after this function returns - and, for a diamond (e.g. an effect fed by two memos
+/// that both read the same signal), multiple independent writes can each try to schedule the same
+/// shared subscriber before its first scheduled run has executed. `scheduled` is what dedupes
+/// across that gap: a node already in it is left alone rather than re-enqueued.
+fn notify_subscribers(id: NodeId) {
+    let subscribers: Vec<NodeId> =
+        RUNTIME.with(|rt| rt.borrow().node(id).subscribers.iter().copied().collect());
+    for subscriber in subscribers {
+        schedule_rerun(subscriber);
+    }
+}
+
+/// Enqueues `id`'s rerun, unless it already has one enqueued and not yet executed.
+fn schedule_rerun(id: NodeId) {
+    let rerun = RUNTIME.with(|rt| {
+        let mut rt = rt.borrow_mut();
+        if !rt.scheduled.insert(id) {
+            return None;
+        }
+        rt.node(id).rerun.clone()
+    });
+    if let Some(rerun) = rerun {
+        rerun();
+    }
+}
+
+/// Clears `id`'s scheduled marker. Must be called from inside `id`'s own rerun body, once its
+/// deferred message actually starts executing, so a write that happens during (or after) this run
+/// can schedule it again.
+fn clear_scheduled(id: NodeId) {
+    RUNTIME.with(|rt| {
+        rt.borrow_mut().scheduled.remove(&id);
+    });
+}
+
+pub struct ReadSignal<T> {
+    id: NodeId,
+    _marker: PhantomData<T>,
+}
+
+impl<T> Clone for ReadSignal<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for ReadSignal<T> {}
+
+impl<T: 'static + DynSend + DynSync> ReadSignal<T> {
+    pub fn get(&self) -> Shared<T> {
+        track_read(self.id);
+        let value = RUNTIME.with(|rt| rt.borrow().node(self.id).value.borrow().clone());
+        value.downcast::<T>().expect("ReadSignal<T> used at the wrong type")
+    }
+}
+
+pub struct WriteSignal<T> {
+    id: NodeId,
+    _marker: PhantomData<T>,
+}
+
+impl<T> Clone for WriteSignal<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for WriteSignal<T> {}
+
+impl<T: 'static + DynSend + DynSync> WriteSignal<T> {
+    pub fn set(&self, value: T) {
+        RUNTIME.with(|rt| {
+            *rt.borrow().node(self.id).value.borrow_mut() = Shared::new(value);
+        });
+        notify_subscribers(self.id);
+    }
+}
+
+/// Creates a reactive signal. Unlike `use_state`, reading it with `ReadSignal::get` inside a
+/// `create_effect`/`create_memo` closure subscribes that closure automatically - no dependency
+/// array required.
+pub fn use_signal<T: 'static + DynSend + DynSync, F>(init: F) -> (ReadSignal<T>, WriteSignal<T>)
+where
+    F: FnOnce() -> T,
+{
+    let id = use_ref(|| RUNTIME.with(|rt| rt.borrow_mut().alloc(Shared::new(init()), None)));
+    let id = *Lock::borrow(&*id);
+    (
+        ReadSignal { id, _marker: PhantomData },
+        WriteSignal { id, _marker: PhantomData },
+    )
+}
+
+/// Runs `f` once and re-runs it whenever a signal it read (via `ReadSignal::get`) changes.
+/// Re-runs are scheduled through the owning component's `process_message`, the same plumbing
+/// `use_effect` uses, so they land as an ordinary Yew update rather than running inline on write.
+pub fn create_effect<F>(f: F)
+where
+    F: Fn() + DynSend + DynSync + 'static,
+{
+    struct UseSignalEffectState {
+        id: Option<NodeId>,
+    }
+    use_hook(
+        move |state: &mut UseSignalEffectState, hook_update| {
+            // Every render re-registers the rerun closure against this render's `f`, the same
+            // way `use_reducer`'s dispatch always closes over the latest render's reducer - only
+            // the very first render actually runs the effect immediately.
+            let first_run = state.id.is_none();
+            let id = *state.id.get_or_insert_with(|| {
+                RUNTIME.with(|rt| rt.borrow_mut().alloc(Shared::new(()), None))
+            });
+            let f = Shared::new(f);
+            let rerun: Shared<NotifyFn> = {
+                let f = f.clone();
+                Shared::new(move || {
+                    let f = f.clone();
+                    hook_update(move |_: &mut UseSignalEffectState| {
+                        // Only here, as the deferred message actually executes, is it safe to
+                        // let a future write schedule this node again.
+                        clear_scheduled(id);
+                        run_tracked(id, || f());
+                        true
+                    })
+                })
+            };
+            RUNTIME.with(|rt| rt.borrow_mut().nodes[id].as_mut().unwrap().rerun = Some(rerun));
+            if first_run {
+                run_tracked(id, || f());
+            }
+        },
+        || UseSignalEffectState { id: None },
+    );
+}
+
+/// A derived signal: `compute` runs once, tracking whatever signals it reads, and is re-run
+/// through the same scheduling path as `create_effect` whenever one of them changes. The result
+/// of each run is stored and exposed as a `ReadSignal`, so memos chain the same way signals do.
+pub fn create_memo<T: 'static + DynSend + DynSync, F>(compute: F) -> ReadSignal<T>
+where
+    F: Fn() -> T + DynSend + DynSync + 'static,
+{
+    struct UseMemoNodeState {
+        id: Option<NodeId>,
+    }
+    let node_state = use_hook(
+        move |state: &mut UseMemoNodeState, hook_update| {
+            let first_run = state.id.is_none();
+            let id = *state.id.get_or_insert_with(|| {
+                RUNTIME.with(|rt| rt.borrow_mut().alloc(Shared::new(()), None))
+            });
+            let compute = Shared::new(compute);
+            let rerun: Shared<NotifyFn> = {
+                let compute = compute.clone();
+                Shared::new(move || {
+                    let compute = compute.clone();
+                    hook_update(move |_: &mut UseMemoNodeState| {
+                        // Only here, as the deferred message actually executes, is it safe to
+                        // let a future write schedule this node again - in particular, before
+                        // `notify_subscribers` below, so a subscriber that also depends on
+                        // whatever this memo depends on directly can still be scheduled fresh.
+                        clear_scheduled(id);
+                        let value = run_tracked(id, || compute());
+                        RUNTIME.with(|rt| {
+                            *rt.borrow().node(id).value.borrow_mut() = Shared::new(value);
+                        });
+                        notify_subscribers(id);
+                        true
+                    })
+                })
+            };
+            RUNTIME.with(|rt| rt.borrow_mut().nodes[id].as_mut().unwrap().rerun = Some(rerun));
+            if first_run {
+                let initial = run_tracked(id, || compute());
+                RUNTIME.with(|rt| {
+                    *rt.borrow().node(id).value.borrow_mut() = Shared::new(initial);
+                });
+            }
+            id
+        },
+        || UseMemoNodeState { id: None },
+    );
+    ReadSignal { id: node_state, _marker: PhantomData }
+}
+
+/// Makes `value` available to this component's descendants via `use_context::<T>()`. Storage
+/// and change-notification are scoped to this specific call-site instance (via a `ProviderId`
+/// that, like a hook slot, stays stable across this component's re-renders) rather than to
+/// `T`'s `TypeId` alone, so a sibling provider of the same `T` elsewhere in the tree changing its
+/// value never notifies this provider's consumers. Must be called from within a function
+/// component's render.
+pub fn provide_context<T: 'static + DynSend + DynSync>(value: Shared<T>) {
+    struct ProvideContextState {
+        id: ProviderId,
+        previous: Option<Shared<AnyValue>>,
+    }
+    let type_id = TypeId::of::<T>();
+    let any_value = value as Shared<AnyValue>;
+
+    let (provider_id, changed) = use_hook(
+        {
+            let any_value = any_value.clone();
+            move |state: &mut ProvideContextState, pretrigger_change_acceptor| {
+                let _ignored = || pretrigger_change_acceptor(|_| false);
+                let changed = match &state.previous {
+                    Some(previous) => !Shared::ptr_eq(previous, &any_value),
+                    None => true,
+                };
+                state.previous = Some(any_value);
+                (state.id, changed)
+            }
+        },
+        || ProvideContextState {
+            id: next_provider_id(),
+            previous: None,
+        },
+    );
+
+    CONTEXT_STACK.with(|stack| {
+        let stack = stack.borrow();
+        let frame = stack
+            .last()
+            .expect("provide_context can only be called during a function component's render");
+        frame.borrow_mut().insert(type_id, (provider_id, any_value));
+    });
+
+    if changed {
+        let subscribers: Vec<Shared<NotifyFn>> = CONTEXT_SUBSCRIBERS.with(|subs| {
+            subs.borrow()
+                .get(&(provider_id, type_id))
+                .map(|subscribers| subscribers.values().cloned().collect())
+                .unwrap_or_default()
+        });
+        for notify in subscribers {
+            notify();
+        }
+    }
+}
+
+/// Looks up the nearest ancestor's `provide_context::<T>()` value, walking the render-time
+/// context stack from the innermost frame outward. Re-renders this component whenever the
+/// specific provider it resolved against calls `provide_context::<T>()` with a changed value.
+/// Returns `None`, with no fallback, when there is no ancestor provider of `T`.
+pub fn use_context<T: 'static + DynSend + DynSync>() -> Option<Shared<T>> {
+    struct UseContextState {
+        subscriber_id: SubscriberId,
+        subscribed_to: Option<ProviderId>,
+        unmount_registered: bool,
+    }
+    let type_id = TypeId::of::<T>();
+
+    let from_stack: Option<(ProviderId, Shared<AnyValue>)> = CONTEXT_STACK.with(|stack| {
+        let stack = stack.borrow();
+        for frame in stack.iter().rev() {
+            if let Some((provider_id, value)) = frame.borrow().get(&type_id) {
+                return Some((*provider_id, value.clone()));
+            }
+        }
+        None
+    });
+    let resolved_provider = from_stack.as_ref().map(|(provider_id, _)| *provider_id);
+
+    // Tracks which provider this consumer is currently subscribed to, outside of `use_hook`'s
+    // managed state, so the unmount callback registered below - which runs with no render in
+    // progress - can still read the *latest* provider instead of whichever one was current the
+    // one time the callback was registered.
+    let subscribed_to_cell: Shared<Lock<Option<ProviderId>>> = use_ref(|| None);
+
+    use_hook(
+        move |state: &mut UseContextState, hook_update| {
+            if !state.unmount_registered {
+                state.unmount_registered = true;
+                let subscribed_to_cell = subscribed_to_cell.clone();
+                let subscriber_id = state.subscriber_id;
+                register_unmount(move || {
+                    if let Some(provider_id) = *Lock::borrow(&*subscribed_to_cell) {
+                        unsubscribe_context(provider_id, type_id, subscriber_id);
+                    }
+                });
+            }
+
+            if state.subscribed_to != resolved_provider {
+                if let Some(old_provider_id) = state.subscribed_to {
+                    unsubscribe_context(old_provider_id, type_id, state.subscriber_id);
+                }
+                state.subscribed_to = resolved_provider;
+                *Lock::borrow_mut(&*subscribed_to_cell) = resolved_provider;
+                if let Some(provider_id) = resolved_provider {
+                    let subscriber_id = state.subscriber_id;
+                    let notify: Shared<NotifyFn> =
+                        Shared::new(move || hook_update(move |_: &mut UseContextState| true));
+                    CONTEXT_SUBSCRIBERS.with(|subs| {
+                        subs.borrow_mut()
+                            .entry((provider_id, type_id))
+                            .or_default()
+                            .insert(subscriber_id, notify);
+                    });
+                }
+            }
+        },
+        || UseContextState {
+            subscriber_id: next_subscriber_id(),
+            subscribed_to: None,
+            unmount_registered: false,
+        },
+    );
+
+    from_stack.map(|(_, value)| value.downcast::<T>().expect("use_context::<T>() type mismatch"))
+}